@@ -0,0 +1,376 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub mod median;
+#[cfg(feature = "serde1")]
+pub mod persist;
+pub mod reservoir;
+
+use core::fmt::Debug;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use median::Median;
+use reservoir::Reservoir;
+
+#[cfg(feature = "std")]
+use std::vec::Vec;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+type DefaultReservoir<T> = std::collections::HashSet<T>;
+
+#[cfg(all(feature = "smallvec", feature = "alloc", not(feature = "std")))]
+type DefaultReservoir<T> = reservoir::SmallVecSet<T>;
+
+#[cfg(all(feature = "alloc", not(feature = "std"), not(feature = "smallvec")))]
+type DefaultReservoir<T> = alloc::collections::BTreeSet<T>;
+
+pub trait Element: Clone + core::hash::Hash + PartialEq + Eq + Debug {}
+impl<T: Clone + core::hash::Hash + PartialEq + Eq + Debug> Element for T {}
+
+/// The main data structure for the [CVM algorithm](https://arxiv.org/abs/2301.10191).
+///
+/// `R` is the random number generator used to decide which elements to keep.
+/// It defaults to [`StdRng`], but any [`Rng`] can be injected via
+/// [`Cvm::with_rng`] (e.g. a seeded `StdRng`) to make runs reproducible.
+///
+/// `S` is the backing store for the retained elements, abstracted behind
+/// [`Reservoir`] so it can be swapped for the environment the sketch runs
+/// in; it defaults to `std`'s `HashSet`. See the [`reservoir`] module.
+#[derive(Debug)]
+pub struct Cvm<T, R = StdRng, S = DefaultReservoir<T>> {
+    capacity: usize,
+    memory: S,
+    rounds: u32,
+    rng: R,
+    _element: core::marker::PhantomData<T>,
+}
+
+impl<T: Debug> Cvm<T, StdRng, DefaultReservoir<T>>
+where
+    DefaultReservoir<T>: Reservoir<T> + Default,
+{
+    pub fn new(capacity: usize) -> Self {
+        Self::with_rng(capacity, StdRng::from_entropy())
+    }
+
+    /// Sizes the sketch so that, over a stream of roughly `stream_len_hint`
+    /// items, `Pr[|estimate - true| > epsilon * true] < delta`, per the CVM
+    /// analysis: `capacity = ceil((12 / epsilon^2) * log2(8 * stream_len_hint / delta))`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `epsilon` or `delta` isn't in `(0.0, 1.0)`.
+    pub fn with_accuracy(epsilon: f64, delta: f64, stream_len_hint: usize) -> Self {
+        assert!(
+            epsilon > 0.0 && epsilon < 1.0,
+            "epsilon must be in (0.0, 1.0), got {epsilon}"
+        );
+        assert!(
+            delta > 0.0 && delta < 1.0,
+            "delta must be in (0.0, 1.0), got {delta}"
+        );
+
+        let capacity = ((12.0 / epsilon.powi(2))
+            * (8.0 * stream_len_hint as f64 / delta).log2())
+        .ceil() as usize;
+        Self::new(capacity)
+    }
+}
+
+impl<T: Debug, R: Rng, S: Reservoir<T> + Default> Cvm<T, R, S> {
+    pub fn with_rng(capacity: usize, rng: R) -> Self {
+        Cvm {
+            capacity,
+            memory: S::default(),
+            rounds: 0,
+            rng,
+            _element: core::marker::PhantomData,
+        }
+    }
+
+    fn should_keep(rng: &mut R, coin_flips: u32) -> bool {
+        (0..coin_flips).all(|_| rng.gen())
+    }
+
+    pub fn estimate(&self) -> usize {
+        let rounds = if self.rounds > 32 { 32 } else { self.rounds };
+        self.memory.len() * 2_usize.pow(rounds)
+    }
+
+    /// Returns `(lo, estimate, hi)`: the point estimate bracketed by the
+    /// `epsilon`-relative confidence interval `estimate * (1 +/- epsilon)`.
+    /// Pass the same `epsilon` the sketch was sized with (e.g. via
+    /// [`Cvm::with_accuracy`]) for the bound to hold with probability
+    /// `1 - delta`.
+    pub fn estimate_with_bounds(&self, epsilon: f64) -> (usize, usize, usize) {
+        let est = self.estimate();
+        let lo = ((est as f64) * (1.0 - epsilon)).max(0.0) as usize;
+        let hi = ((est as f64) * (1.0 + epsilon)).ceil() as usize;
+        (lo, est, hi)
+    }
+
+    pub fn extend<I>(&mut self, iter: I)
+    where
+        I: Iterator<Item = T>,
+    {
+        for i in iter {
+            self.add(i);
+        }
+    }
+
+    pub fn add(&mut self, value: T) {
+        let keep = self.rounds == 0 || Self::should_keep(&mut self.rng, self.rounds);
+        if keep {
+            self.memory.insert(value);
+        } else if self.memory.contains(&value) {
+            self.memory.remove(&value);
+        }
+
+        if self.memory.len() >= self.capacity {
+            self.sweep();
+        }
+    }
+
+    fn sweep(&mut self) {
+        let rng = &mut self.rng;
+        self.memory.retain(|_| Self::should_keep(rng, 1));
+        self.rounds += 1;
+    }
+}
+
+impl<T: Debug + Clone, R: Rng, S: Reservoir<T> + Default + Clone> Cvm<T, R, S> {
+    /// Merges `other`'s surviving elements into `self`, as if both sketches
+    /// had been built over disjoint shards of the same stream.
+    ///
+    /// Each retained element in a sketch at round `r` was kept independently
+    /// with probability `2^-r`. Since `self` and `other` may be at different
+    /// rounds, merging first brings whichever is behind up to
+    /// `max(self.rounds, other.rounds)` by replaying sweeps on its memory
+    /// (preserving that per-round keep probability), unions the two memories,
+    /// then sweeps the union back down below `capacity`.
+    pub fn merge(&mut self, other: &Cvm<T, R, S>) {
+        while self.rounds < other.rounds {
+            self.sweep();
+        }
+
+        let mut other_memory = other.memory.clone();
+        let mut other_rounds = other.rounds;
+        let rng = &mut self.rng;
+        while other_rounds < self.rounds {
+            other_memory.retain(|_| Self::should_keep(rng, 1));
+            other_rounds += 1;
+        }
+
+        self.memory.extend(other_memory);
+
+        while self.memory.len() >= self.capacity {
+            self.sweep();
+        }
+    }
+}
+
+/// The failure probability used when a caller doesn't pick one explicitly.
+const DEFAULT_DELTA: f64 = 0.05;
+
+/// Not taken from the paper, just me playing around.
+///
+/// Aggregates its instances with a median-of-means estimator: the instance
+/// estimates are split into `k` groups, each group is averaged, and the
+/// median across those group-means is returned. `delta` controls `k` (larger
+/// `k`, i.e. smaller `delta`, trades more instances for a tighter failure
+/// probability on the final estimate).
+pub struct CombinedCvm<T, R = StdRng, S = DefaultReservoir<T>> {
+    cvms: Vec<Cvm<T, R, S>>,
+    delta: f64,
+}
+
+impl<T: Element> CombinedCvm<T, StdRng, DefaultReservoir<T>>
+where
+    DefaultReservoir<T>: Reservoir<T> + Default,
+{
+    /// # Panics
+    ///
+    /// Panics if `len` is 0.
+    pub fn new(capacity: usize, len: usize) -> Self {
+        Self::with_delta(capacity, len, DEFAULT_DELTA)
+    }
+
+    /// # Panics
+    ///
+    /// Panics if `len` is 0.
+    pub fn with_delta(capacity: usize, len: usize, delta: f64) -> Self {
+        Self::with_rng(capacity, len, delta, StdRng::from_entropy())
+    }
+
+    /// Derives each instance's seed by XOR-mixing `base_seed` with the
+    /// instance index, so the whole multi-instance estimate is reproducible
+    /// from a single `u64` (the same seed-fan-out trick used by
+    /// casper-node's `ds.rs`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is 0.
+    pub fn with_seed(capacity: usize, len: usize, delta: f64, base_seed: u64) -> Self {
+        if len == 0 {
+            panic!("Length must be greater than 0");
+        }
+        CombinedCvm {
+            cvms: (0..len)
+                .map(|i| Cvm::with_rng(capacity, StdRng::seed_from_u64(base_seed ^ i as u64)))
+                .collect(),
+            delta,
+        }
+    }
+}
+
+impl<T: Element, R: Rng + SeedableRng, S: Reservoir<T> + Default> CombinedCvm<T, R, S> {
+    /// Builds each instance with its own stream seeded from `rng`, so
+    /// instances don't share hidden global state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is 0.
+    pub fn with_rng(capacity: usize, len: usize, delta: f64, mut rng: R) -> Self {
+        if len == 0 {
+            panic!("Length must be greater than 0");
+        }
+        CombinedCvm {
+            cvms: (0..len)
+                .map(|_| {
+                    Cvm::with_rng(
+                        capacity,
+                        R::from_rng(&mut rng).expect("failed to seed child RNG"),
+                    )
+                })
+                .collect(),
+            delta,
+        }
+    }
+}
+
+impl<T: Element, R: Rng, S: Reservoir<T> + Default> CombinedCvm<T, R, S> {
+    pub fn extend<I>(&mut self, iter: I)
+    where
+        I: Iterator<Item = T>,
+    {
+        for i in iter {
+            self.add(&i);
+        }
+    }
+
+    pub fn add(&mut self, value: &T) {
+        for c in self.cvms.iter_mut() {
+            c.add(value.clone());
+        }
+    }
+
+    /// Merges each of `other`'s instances into the corresponding instance of
+    /// `self`, instance-for-instance. See [`Cvm::merge`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` and `other` don't have the same number of instances,
+    /// since otherwise instances past the shorter side's length would be
+    /// silently dropped from the merge.
+    pub fn merge(&mut self, other: &CombinedCvm<T, R, S>)
+    where
+        S: Clone,
+    {
+        assert_eq!(
+            self.cvms.len(),
+            other.cvms.len(),
+            "merge requires both CombinedCvm instances to have the same instance count"
+        );
+        for (c, o) in self.cvms.iter_mut().zip(other.cvms.iter()) {
+            c.merge(o);
+        }
+    }
+
+    /// The number of groups `k` the instance estimates are partitioned into
+    /// before averaging, `k = ceil(log(1 / delta))`, clamped to at least one
+    /// group and at most one group per instance.
+    fn group_count(&self) -> usize {
+        let k = (1.0 / self.delta).ln().ceil() as usize;
+        k.clamp(1, self.cvms.len().max(1))
+    }
+
+    pub fn estimate(&self) -> usize {
+        let ests = self.cvms.iter().map(Cvm::estimate).collect::<Vec<_>>();
+        let groups = self.group_count();
+        // Exactly `groups` contiguous groups whose sizes differ by at most one.
+        let (base, extra) = (ests.len() / groups, ests.len() % groups);
+        let mut rest = ests.as_slice();
+
+        let group_means: Median<usize> = (0..groups)
+            .map(|g| {
+                let (chunk, tail) = rest.split_at(base + usize::from(g < extra));
+                rest = tail;
+                chunk.iter().sum::<usize>() / chunk.len().max(1)
+            })
+            .collect();
+
+        group_means.median().copied().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded_cvm_is_reproducible() {
+        let build = || {
+            let mut c: Cvm<u64> = Cvm::with_rng(50, StdRng::seed_from_u64(42));
+            c.extend(0..2000u64);
+            c.estimate()
+        };
+
+        let estimate = build();
+        assert_eq!(estimate, build(), "same seed must yield the same estimate");
+        assert_eq!(estimate, 2112, "golden value for seed 42 over 0..2000");
+    }
+
+    #[test]
+    fn seeded_combined_cvm_is_reproducible() {
+        let build = || {
+            let mut c: CombinedCvm<u64> = CombinedCvm::with_seed(50, 8, DEFAULT_DELTA, 7);
+            c.extend(0..2000u64);
+            c.estimate()
+        };
+
+        let estimate = build();
+        assert_eq!(estimate, build(), "same base seed must yield the same estimate");
+        assert_eq!(estimate, 2197, "golden value for base seed 7 over 0..2000");
+    }
+
+    #[test]
+    fn merge_across_mismatched_rounds_preserves_invariant() {
+        let mut few_rounds: Cvm<u64> = Cvm::with_rng(30, StdRng::seed_from_u64(1));
+        few_rounds.extend(0..500u64);
+
+        let mut many_rounds: Cvm<u64> = Cvm::with_rng(10, StdRng::seed_from_u64(2));
+        many_rounds.extend(500..2500u64);
+
+        assert!(
+            many_rounds.rounds > few_rounds.rounds,
+            "test setup should produce sketches at different rounds"
+        );
+
+        few_rounds.merge(&many_rounds);
+
+        assert!(
+            few_rounds.memory.len() < few_rounds.capacity,
+            "merge must sweep the union back below capacity"
+        );
+        assert!(
+            few_rounds.rounds >= many_rounds.rounds,
+            "merge must bring the lower-round sketch up to at least the other's rounds"
+        );
+        assert!(few_rounds.estimate() > 0);
+    }
+}
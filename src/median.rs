@@ -0,0 +1,101 @@
+#[cfg(feature = "std")]
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::collections::BinaryHeap;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use core::cmp::Reverse;
+
+/// A streaming median accumulator.
+///
+/// Keeps a max-heap of the lower half of the values seen so far and a
+/// min-heap of the upper half, rebalancing on each push so the two halves
+/// never differ in size by more than one. That gives O(1) median reads and
+/// O(log n) inserts, with no need to buffer and sort the whole stream.
+#[derive(Debug)]
+pub struct Median<T: Ord> {
+    // Max-heap: largest of the lower half sits on top.
+    lower: BinaryHeap<T>,
+    // Min-heap: smallest of the upper half sits on top.
+    upper: BinaryHeap<Reverse<T>>,
+}
+
+impl<T: Ord> Median<T> {
+    pub fn new() -> Self {
+        Median {
+            lower: BinaryHeap::new(),
+            upper: BinaryHeap::new(),
+        }
+    }
+
+    pub fn push(&mut self, value: T) {
+        match self.lower.peek() {
+            Some(max_lower) if value > *max_lower => self.upper.push(Reverse(value)),
+            _ => self.lower.push(value),
+        }
+
+        if self.lower.len() > self.upper.len() + 1 {
+            if let Some(v) = self.lower.pop() {
+                self.upper.push(Reverse(v));
+            }
+        } else if self.upper.len() > self.lower.len() {
+            if let Some(Reverse(v)) = self.upper.pop() {
+                self.lower.push(v);
+            }
+        }
+    }
+
+    /// The median of the values pushed so far, or `None` if empty.
+    ///
+    /// For an even count this returns the lower of the two middle values
+    /// rather than their average, so it works for any `Ord` type and not
+    /// just ones with a meaningful midpoint.
+    pub fn median(&self) -> Option<&T> {
+        self.lower.peek()
+    }
+}
+
+impl<T: Ord> Default for Median<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for Median<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut median = Median::new();
+        for value in iter {
+            median.push(value);
+        }
+        median
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Median;
+
+    #[test]
+    fn empty_has_no_median() {
+        let median: Median<i32> = Median::new();
+        assert_eq!(median.median(), None);
+    }
+
+    #[test]
+    fn single_element_is_its_own_median() {
+        let median: Median<i32> = [5].into_iter().collect();
+        assert_eq!(median.median(), Some(&5));
+    }
+
+    #[test]
+    fn even_count_returns_lower_of_two_middle_values() {
+        let median: Median<i32> = [1, 2, 3, 4].into_iter().collect();
+        assert_eq!(median.median(), Some(&2));
+    }
+
+    #[test]
+    fn odd_count_returns_middle_value() {
+        let median: Median<i32> = [1, 2, 3, 4, 5].into_iter().collect();
+        assert_eq!(median.median(), Some(&3));
+    }
+}
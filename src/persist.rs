@@ -0,0 +1,186 @@
+//! On-disk checkpoints for a running [`Cvm`]/[`CombinedCvm`] sketch, behind
+//! the optional `serde1` feature (named the way `rand`'s `seq/index.rs` names
+//! its own serde feature).
+//!
+//! A snapshot carries `capacity`, `rounds`, and the retained `memory` set —
+//! not RNG state, since resuming the CVM algorithm's accuracy guarantee only
+//! needs *some* forward-looking randomness, not a particular prior stream.
+//! [`Cvm::load`] and [`CombinedCvm::load`] take a freshly supplied `rng` to
+//! resume `extend`-ing with.
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::reservoir::Reservoir;
+use crate::{CombinedCvm, Cvm, Element};
+
+#[cfg(feature = "std")]
+use std::vec::Vec;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+
+/// Returned when a deserialized snapshot violates the invariant that a
+/// sketch never retains more than `capacity` elements: a snapshot with
+/// `memory.len() > capacity` indicates a corrupted blob.
+#[derive(Debug)]
+pub enum LoadError {
+    MemoryExceedsCapacity { capacity: usize, len: usize },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::MemoryExceedsCapacity { capacity, len } => write!(
+                f,
+                "corrupt snapshot: memory has {len} elements but capacity is {capacity}"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LoadError {}
+
+/// The serialized form of a [`Cvm`] checkpoint. See the [module docs](self).
+#[derive(Serialize, Deserialize)]
+pub struct CvmSnapshot<T, S> {
+    capacity: usize,
+    rounds: u32,
+    memory: S,
+    #[serde(skip)]
+    _element: PhantomData<T>,
+}
+
+impl<T, R: Rng, S: Reservoir<T> + Default + Clone> Cvm<T, R, S> {
+    /// Captures the sketch's persistent state (not its RNG) for checkpointing.
+    pub fn save(&self) -> CvmSnapshot<T, S> {
+        CvmSnapshot {
+            capacity: self.capacity,
+            rounds: self.rounds,
+            memory: self.memory.clone(),
+            _element: PhantomData,
+        }
+    }
+}
+
+impl<T, R: Rng, S: Reservoir<T> + Default> Cvm<T, R, S> {
+    /// Restores a checkpoint saved via [`Cvm::save`], resuming with `rng`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LoadError`] if `snapshot.memory.len() > snapshot.capacity`,
+    /// which a real `sweep` never produces.
+    pub fn load(snapshot: CvmSnapshot<T, S>, rng: R) -> Result<Self, LoadError> {
+        if snapshot.memory.len() > snapshot.capacity {
+            return Err(LoadError::MemoryExceedsCapacity {
+                capacity: snapshot.capacity,
+                len: snapshot.memory.len(),
+            });
+        }
+
+        Ok(Cvm {
+            capacity: snapshot.capacity,
+            memory: snapshot.memory,
+            rounds: snapshot.rounds,
+            rng,
+            _element: PhantomData,
+        })
+    }
+}
+
+/// The serialized form of a [`CombinedCvm`] checkpoint. See the
+/// [module docs](self).
+#[derive(Serialize, Deserialize)]
+pub struct CombinedCvmSnapshot<T, S> {
+    cvms: Vec<CvmSnapshot<T, S>>,
+    delta: f64,
+}
+
+impl<T: Element, R: Rng, S: Reservoir<T> + Default + Clone> CombinedCvm<T, R, S> {
+    /// Captures every instance's persistent state for checkpointing.
+    pub fn save(&self) -> CombinedCvmSnapshot<T, S> {
+        CombinedCvmSnapshot {
+            cvms: self.cvms.iter().map(Cvm::save).collect(),
+            delta: self.delta,
+        }
+    }
+}
+
+impl<T: Element, R: Rng + SeedableRng, S: Reservoir<T> + Default> CombinedCvm<T, R, S> {
+    /// Restores a checkpoint saved via [`CombinedCvm::save`], deriving each
+    /// instance's resumed RNG from `rng` (see [`CombinedCvm::with_rng`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LoadError`] if any instance's snapshot fails the same check
+    /// as [`Cvm::load`].
+    pub fn load(snapshot: CombinedCvmSnapshot<T, S>, mut rng: R) -> Result<Self, LoadError> {
+        let cvms = snapshot
+            .cvms
+            .into_iter()
+            .map(|s| Cvm::load(s, R::from_rng(&mut rng).expect("failed to seed child RNG")))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(CombinedCvm {
+            cvms,
+            delta: snapshot.delta,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use std::collections::HashSet;
+
+    #[test]
+    fn cvm_save_load_round_trips_through_json() {
+        let mut cvm: Cvm<u64, StdRng, HashSet<u64>> = Cvm::with_rng(20, StdRng::seed_from_u64(9));
+        cvm.extend(0..200u64);
+
+        let json = serde_json::to_string(&cvm.save()).unwrap();
+        let snapshot: CvmSnapshot<u64, HashSet<u64>> = serde_json::from_str(&json).unwrap();
+        let restored = Cvm::load(snapshot, StdRng::seed_from_u64(99)).unwrap();
+
+        assert_eq!(restored.estimate(), cvm.estimate());
+    }
+
+    #[test]
+    fn cvm_load_rejects_memory_exceeding_capacity() {
+        let snapshot: CvmSnapshot<u64, HashSet<u64>> = CvmSnapshot {
+            capacity: 2,
+            rounds: 0,
+            memory: HashSet::from([1, 2, 3]),
+            _element: PhantomData,
+        };
+
+        let err = Cvm::<u64, StdRng, HashSet<u64>>::load(snapshot, StdRng::seed_from_u64(1))
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            LoadError::MemoryExceedsCapacity {
+                capacity: 2,
+                len: 3
+            }
+        ));
+    }
+
+    #[test]
+    fn combined_cvm_save_load_round_trips() {
+        let mut c: CombinedCvm<u64, StdRng, HashSet<u64>> =
+            CombinedCvm::with_rng(20, 4, 0.05, StdRng::seed_from_u64(3));
+        c.extend(0..400u64);
+
+        let json = serde_json::to_string(&c.save()).unwrap();
+        let snapshot: CombinedCvmSnapshot<u64, HashSet<u64>> =
+            serde_json::from_str(&json).unwrap();
+        let restored = CombinedCvm::load(snapshot, StdRng::seed_from_u64(4)).unwrap();
+
+        assert_eq!(restored.estimate(), c.estimate());
+    }
+}
@@ -0,0 +1,163 @@
+//! Pluggable storage for the elements a [`Cvm`](crate::Cvm) sketch currently
+//! retains.
+//!
+//! `Cvm` doesn't care whether its retained set is hashed, ordered, or just a
+//! small linear buffer — it only needs insert/remove/contains/retain. Putting
+//! that behind the [`Reservoir`] trait lets the sketch swap backends for the
+//! environment it runs in: `std`'s `HashSet` by default, a `BTreeSet` for
+//! `no_std + alloc` targets without a hasher, or a `smallvec`-backed linear
+//! scan that stays inline (no heap allocation) for small `capacity`.
+
+/// Storage backend for a [`Cvm`](crate::Cvm)'s retained elements.
+pub trait Reservoir<T>: Default + IntoIterator<Item = T> {
+    fn insert(&mut self, value: T) -> bool;
+    fn remove(&mut self, value: &T) -> bool;
+    fn contains(&self, value: &T) -> bool;
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn retain<F: FnMut(&T) -> bool>(&mut self, f: F);
+
+    /// Inserts every value from `iter`, used by [`Cvm::merge`](crate::Cvm::merge)
+    /// to union two reservoirs.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+mod std_backend {
+    use super::Reservoir;
+    use std::collections::HashSet;
+    use std::hash::Hash;
+
+    impl<T: Hash + Eq> Reservoir<T> for HashSet<T> {
+        fn insert(&mut self, value: T) -> bool {
+            HashSet::insert(self, value)
+        }
+
+        fn remove(&mut self, value: &T) -> bool {
+            HashSet::remove(self, value)
+        }
+
+        fn contains(&self, value: &T) -> bool {
+            HashSet::contains(self, value)
+        }
+
+        fn len(&self) -> usize {
+            HashSet::len(self)
+        }
+
+        fn retain<F: FnMut(&T) -> bool>(&mut self, f: F) {
+            HashSet::retain(self, f)
+        }
+    }
+}
+
+/// `BTreeSet`-backed storage for `no_std + alloc` targets, where no default
+/// hasher is available.
+#[cfg(feature = "alloc")]
+mod alloc_backend {
+    use super::Reservoir;
+    use alloc::collections::BTreeSet;
+
+    impl<T: Ord> Reservoir<T> for BTreeSet<T> {
+        fn insert(&mut self, value: T) -> bool {
+            BTreeSet::insert(self, value)
+        }
+
+        fn remove(&mut self, value: &T) -> bool {
+            BTreeSet::remove(self, value)
+        }
+
+        fn contains(&self, value: &T) -> bool {
+            BTreeSet::contains(self, value)
+        }
+
+        fn len(&self) -> usize {
+            BTreeSet::len(self)
+        }
+
+        fn retain<F: FnMut(&T) -> bool>(&mut self, f: F) {
+            BTreeSet::retain(self, f)
+        }
+    }
+}
+
+/// Linear, `smallvec`-backed storage that keeps elements inline for the
+/// common case of a small `capacity`, avoiding heap allocation entirely
+/// until the reservoir spills past its inline capacity.
+#[cfg(feature = "smallvec")]
+mod smallvec_backend {
+    use super::Reservoir;
+    use smallvec::SmallVec;
+    #[cfg(feature = "serde1")]
+    use serde::{Deserialize, Serialize};
+
+    /// Elements up to this count live inline before `SmallVecSet` spills
+    /// onto the heap.
+    const INLINE_CAPACITY: usize = 16;
+
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+    pub struct SmallVecSet<T>(SmallVec<[T; INLINE_CAPACITY]>);
+
+    // Hand-rolled rather than `#[derive(Default)]`: the derive would add a
+    // spurious `T: Default` bound even though an empty `SmallVec` never
+    // needs one.
+    impl<T> Default for SmallVecSet<T> {
+        fn default() -> Self {
+            SmallVecSet(SmallVec::new())
+        }
+    }
+
+    impl<T: PartialEq> Reservoir<T> for SmallVecSet<T> {
+        fn insert(&mut self, value: T) -> bool {
+            if self.0.contains(&value) {
+                false
+            } else {
+                self.0.push(value);
+                true
+            }
+        }
+
+        fn remove(&mut self, value: &T) -> bool {
+            match self.0.iter().position(|v| v == value) {
+                Some(pos) => {
+                    self.0.swap_remove(pos);
+                    true
+                }
+                None => false,
+            }
+        }
+
+        fn contains(&self, value: &T) -> bool {
+            self.0.contains(value)
+        }
+
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+            self.0.retain(|v| f(v));
+        }
+    }
+
+    impl<T> IntoIterator for SmallVecSet<T> {
+        type Item = T;
+        type IntoIter = smallvec::IntoIter<[T; INLINE_CAPACITY]>;
+
+        fn into_iter(self) -> Self::IntoIter {
+            self.0.into_iter()
+        }
+    }
+}
+
+#[cfg(feature = "smallvec")]
+pub use smallvec_backend::SmallVecSet;